@@ -0,0 +1,94 @@
+//! Address-related types used by the `address` command.
+
+use bitcoin::{Address, Network, PublicKey, Script};
+use secp256k1::XOnlyPublicKey;
+use serde::{Deserialize, Serialize};
+
+use crate::tx::OutputScriptInfo;
+use crate::HexBytes;
+
+/// All the addresses that can be derived from a given pubkey or script.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Addresses {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2pkh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2wpkh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2shwpkh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2sh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2wsh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2shwsh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2tr: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub taproot_merkle_root: Option<HexBytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub taproot_script_leaves: Option<Vec<TapLeafInfo>>,
+}
+
+impl Addresses {
+	/// Derive all the addresses that can be made directly from a public key.
+	///
+	/// The segwit variants are only populated when `pubkey` is compressed: an uncompressed
+	/// public key can never form a valid segwit output, so [bitcoin::Address::p2wpkh] and
+	/// [bitcoin::Address::p2shwpkh] fail for it and those fields are left `None`.
+	pub fn from_pubkey(pubkey: &PublicKey, network: Network) -> Addresses {
+		Addresses {
+			p2pkh: Some(Address::p2pkh(pubkey, network)),
+			p2wpkh: Address::p2wpkh(pubkey, network).ok(),
+			p2shwpkh: Address::p2shwpkh(pubkey, network).ok(),
+			..Default::default()
+		}
+	}
+
+	/// Derive all the addresses that can be made directly from a script, treating it as a
+	/// redeem resp. witness script.
+	pub fn from_script(script: &Script, network: Network) -> Addresses {
+		Addresses {
+			p2sh: Some(Address::p2sh(script, network).expect("script too large for p2sh")),
+			p2wsh: Some(Address::p2wsh(script, network)),
+			p2shwsh: Some(Address::p2shwsh(script, network)),
+			..Default::default()
+		}
+	}
+}
+
+/// The spending data for a single leaf in a taproot script tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TapLeafInfo {
+	/// The leaf script, in hex.
+	pub script: HexBytes,
+	/// The BIP-341 TapLeaf hash of [script].
+	pub leaf_hash: HexBytes,
+	/// The control block needed to spend through this leaf.
+	pub control_block: HexBytes,
+}
+
+/// Information about an address, to be used in `address inspect`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressInfo {
+	/// The networks this address could belong to.
+	///
+	/// Bech32 testnet and signet addresses share the `tb` human-readable prefix, so a single
+	/// address string can't always be resolved to a single network.
+	pub possible_networks: Vec<Network>,
+	pub script_pub_key: OutputScriptInfo,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub type_: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pubkey_hash: Option<bitcoin::PubkeyHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub script_hash: Option<bitcoin::ScriptHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub witness_pubkey_hash: Option<bitcoin::WPubkeyHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub witness_script_hash: Option<bitcoin::WScriptHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub witness_program_version: Option<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub taproot_output_key: Option<XOnlyPublicKey>,
+}
@@ -0,0 +1,25 @@
+//! Extension-trait style wrappers around the [crate::cmd] arg helpers, used by the
+//! subcommands that take their `clap::ArgMatches` by method call instead of free function.
+
+use bitcoin::Network;
+use serde::Serialize;
+
+use crate::cmd;
+
+pub(crate) use cmd::{arg, opt, opt_yaml, opts_networks};
+
+/// Extension methods on [clap::ArgMatches] used by the `address`/`script` subcommands.
+pub trait ArgMatchesExt {
+	fn network(&self) -> Network;
+	fn print_output<T: Serialize>(&self, info: &T);
+}
+
+impl<'a> ArgMatchesExt for clap::ArgMatches<'a> {
+	fn network(&self) -> Network {
+		cmd::network(self)
+	}
+
+	fn print_output<T: Serialize>(&self, info: &T) {
+		cmd::print_output(self, info)
+	}
+}
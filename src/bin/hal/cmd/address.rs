@@ -1,14 +1,70 @@
 
 use std::str::FromStr;
 
-use bitcoin::hashes::Hash;
+use bitcoin::blockdata::opcodes::all::OP_PUSHNUM_1;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::consensus::encode::Encodable;
 use bitcoin::hashes::hex::FromHex;
-use bitcoin::{Address, PublicKey, WPubkeyHash, WScriptHash};
-use clap;
+use bitcoin::{Address, Network, PublicKey, Script, VarInt};
 
 use hal;
 
 use crate::prelude::*;
+use crate::util::tagged_hash;
+
+/// The BIP-341 TapLeaf hash of a script, using the default leaf version 0xc0.
+fn tap_leaf_hash(script: &Script) -> [u8; 32] {
+	let mut data = vec![0xc0u8];
+	VarInt(script.len() as u64).consensus_encode(&mut data).expect("vec doesn't error");
+	data.extend_from_slice(script.as_bytes());
+	tagged_hash("TapLeaf", &data)
+}
+
+/// The BIP-341 TapBranch hash of two (lexicographically sorted) child hashes.
+fn tap_branch_hash(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+	let (left, right) = if a <= b { (a, b) } else { (b, a) };
+	let mut data = Vec::with_capacity(64);
+	data.extend_from_slice(&left);
+	data.extend_from_slice(&right);
+	tagged_hash("TapBranch", &data)
+}
+
+/// One node while folding the leaf scripts into a script tree, tracking which of the
+/// original leaves (by index) are contained in its subtree.
+struct TreeNode {
+	hash: [u8; 32],
+	leaves: Vec<usize>,
+}
+
+/// Build a BIP-341 script tree from the given leaf scripts (in the order they were
+/// provided) and return the merkle root together with each leaf's merkle path, i.e.
+/// the list of sibling hashes from the leaf up to the root.
+///
+/// Leaves are folded left-to-right: the first two leaves form the deepest branch and
+/// each subsequent leaf is combined with the running branch, so earlier leaves end up
+/// with longer merkle paths.
+fn build_script_tree(scripts: &[Script]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+	let mut paths: Vec<Vec<[u8; 32]>> = vec![Vec::new(); scripts.len()];
+	let mut nodes: Vec<TreeNode> =
+		scripts.iter().enumerate().map(|(i, s)| TreeNode { hash: tap_leaf_hash(s), leaves: vec![i] }).collect();
+
+	while nodes.len() > 1 {
+		let a = nodes.remove(0);
+		let b = nodes.remove(0);
+		for &i in &a.leaves {
+			paths[i].push(b.hash);
+		}
+		for &i in &b.leaves {
+			paths[i].push(a.hash);
+		}
+		let hash = tap_branch_hash(a.hash, b.hash);
+		let mut leaves = a.leaves;
+		leaves.extend(b.leaves);
+		nodes.insert(0, TreeNode { hash, leaves });
+	}
+
+	(nodes[0].hash, paths)
+}
 
 lazy_static! {
 	/// The H point as used in BIP-341 which is constructed by taking the hash
@@ -26,6 +82,32 @@ fn nums(entropy: secp256k1::Scalar) -> secp256k1::PublicKey {
 	NUMS_H.add_exp_tweak(&SECP, &entropy).expect("invalid NUMS entropy")
 }
 
+/// Tweak an internal key with a script tree's merkle root per BIP-341, returning the
+/// (x-only) internal key, the tweaked output key, and the output key's parity bit.
+fn tweak_taproot_key(
+	internal_pubkey: secp256k1::PublicKey,
+	merkle_root: [u8; 32],
+) -> (secp256k1::XOnlyPublicKey, secp256k1::XOnlyPublicKey, u8) {
+	let internal_key = secp256k1::XOnlyPublicKey::from(internal_pubkey);
+	let tweak_data = [&internal_key.serialize()[..], &merkle_root[..]].concat();
+	let tweak =
+		secp256k1::Scalar::from_be_bytes(tagged_hash("TapTweak", &tweak_data)).expect("invalid tweak");
+	let (output_key, parity) = internal_key.add_tweak(&SECP, &tweak).expect("invalid tweak");
+	(internal_key, output_key, parity.to_u8())
+}
+
+/// Build the control block needed to spend through a script tree leaf with the given
+/// merkle path, per BIP-341.
+fn leaf_control_block(internal_key: secp256k1::XOnlyPublicKey, parity: u8, path: &[[u8; 32]]) -> Vec<u8> {
+	let mut control_block = Vec::with_capacity(33 + 32 * path.len());
+	control_block.push(0xc0 | parity);
+	control_block.extend_from_slice(&internal_key.serialize());
+	for sibling in path {
+		control_block.extend_from_slice(sibling);
+	}
+	control_block
+}
+
 pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand_group("address", "work with addresses")
 		.subcommand(cmd_create())
@@ -34,8 +116,8 @@ pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 
 pub fn execute<'a>(args: &clap::ArgMatches<'a>) {
 	match args.subcommand() {
-		("create", Some(ref m)) => exec_create(&m),
-		("inspect", Some(ref m)) => exec_inspect(&m),
+		("create", Some(m)) => exec_create(m),
+		("inspect", Some(m)) => exec_inspect(m),
 		(_, _) => unreachable!("clap prints help"),
 	};
 }
@@ -44,7 +126,14 @@ fn cmd_create<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand("create", "create addresses").args(&args::opts_networks()).args(&[
 		args::opt_yaml(),
 		args::opt("pubkey", "a public key in hex").takes_value(true).required(false),
-		args::opt("script", "a script in hex").takes_value(true).required(false),
+		args::opt("script", "a script in hex; can be given multiple times to build a taproot \
+			script tree with one leaf per script")
+			.takes_value(true).required(false).multiple(true).number_of_values(1),
+		args::opt(
+			"tap-leaf-file",
+			"a file with additional taproot leaf scripts in hex, one per line, to add to \
+			the scripts given with --script",
+		).takes_value(true).required(false),
 		args::opt(
 			"nums-internal-key-h",
 			"use the H NUMS key from BIP-341 for p2tr address when using --script",
@@ -66,13 +155,28 @@ fn exec_create<'a>(args: &clap::ArgMatches<'a>) {
 
 	if let Some(pubkey_hex) = args.value_of("pubkey") {
 		let pubkey = pubkey_hex.parse::<PublicKey>().expect("invalid pubkey");
+		if !pubkey.compressed {
+			eprintln!(
+				"Note: pubkey is uncompressed, omitting the segwit address types because an \
+				uncompressed pubkey can never form a valid segwit output.",
+			);
+		}
 		let addr = hal::address::Addresses::from_pubkey(&pubkey, network);
 		args.print_output(&addr)
-	} else if let Some(script_hex) = args.value_of("script") {
-		let script_bytes = hex::decode(script_hex).expect("invalid script hex");
-		let script = script_bytes.into();
-
-		let mut ret = hal::address::Addresses::from_script(&script, network);
+	} else if args.is_present("script") {
+		let scripts: Vec<Script> = args
+			.values_of("script")
+			.expect("checked with is_present")
+			.map(|s| hex::decode(s).expect("invalid script hex").into())
+			.chain(args.value_of("tap-leaf-file").into_iter().flat_map(|path| {
+				let content = std::fs::read_to_string(path).expect("failed to read tap-leaf-file");
+				content
+					.lines()
+					.filter(|l| !l.trim().is_empty())
+					.map(|l| hex::decode(l.trim()).expect("invalid script hex in tap-leaf-file").into())
+					.collect::<Vec<Script>>()
+			}))
+			.collect();
 
 		// If the user provided NUMS information we can add a p2tr address.
 		if util::more_than_one(&[
@@ -95,9 +199,49 @@ fn exec_create<'a>(args: &clap::ArgMatches<'a>) {
 		} else {
 			None
 		};
+
+		if scripts.len() > 1 && nums.is_none() {
+			println!(
+				"Multiple leaf scripts were given, but no NUMS internal key: provide \
+				--nums-internal-key-h, --nums-internal-key or --nums-internal-key-entropy \
+				to build a taproot script tree over them.\n",
+			);
+			cmd_create().print_help().unwrap();
+			std::process::exit(1);
+		}
+
+		// With a single leaf script, the script also doubles as a plain redeem/witness
+		// script, so the p2sh/p2wsh/p2shwsh addresses are meaningful on their own. With
+		// multiple leaves there is no single script to derive those from, so we only fill
+		// in the taproot fields below.
+		let mut ret = if scripts.len() == 1 {
+			hal::address::Addresses::from_script(&scripts[0], network)
+		} else {
+			hal::address::Addresses::default()
+		};
+
 		if let Some(pk) = nums {
-			let spk = script.to_v1_p2tr(&SECP, pk.into());
+			let (merkle_root, paths) = build_script_tree(&scripts);
+			let (internal_key, output_key, parity) = tweak_taproot_key(pk, merkle_root);
+
+			let spk = Builder::new()
+				.push_opcode(OP_PUSHNUM_1)
+				.push_slice(&output_key.serialize())
+				.into_script();
 			ret.p2tr = Some(Address::from_script(&spk, network).unwrap());
+
+			ret.taproot_merkle_root = Some(merkle_root.to_vec().into());
+			ret.taproot_script_leaves = Some(
+				scripts
+					.iter()
+					.zip(paths)
+					.map(|(script, path)| hal::address::TapLeafInfo {
+						script: script.to_bytes().into(),
+						leaf_hash: tap_leaf_hash(script).to_vec().into(),
+						control_block: leaf_control_block(internal_key, parity, &path).into(),
+					})
+					.collect(),
+			);
 		}
 
 		args.print_output(&ret)
@@ -117,56 +261,84 @@ fn exec_inspect<'a>(args: &clap::ArgMatches<'a>) {
 	let address: Address = address_str.parse().expect("invalid address format");
 	let script_pk = address.script_pubkey();
 
-	let mut info = hal::address::AddressInfo {
-		network: address.network,
-		script_pub_key: hal::tx::OutputScriptInfo {
-			hex: Some(script_pk.to_bytes().into()),
-			asm: Some(script_pk.asm()),
-			address: None,
-			type_: None,
-		},
-		type_: None,
-		pubkey_hash: None,
-		script_hash: None,
-		witness_pubkey_hash: None,
-		witness_script_hash: None,
-		witness_program_version: None,
-	};
-
 	use bitcoin::util::address::Payload;
-	match address.payload {
-		Payload::PubkeyHash(pkh) => {
-			info.type_ = Some("p2pkh".to_owned());
-			info.pubkey_hash = Some(pkh);
-		}
-		Payload::ScriptHash(sh) => {
-			info.type_ = Some("p2sh".to_owned());
-			info.script_hash = Some(sh);
+	// Testnet and signet bech32 addresses share the `tb` human-readable prefix, so a bare
+	// `tb1...` address can't be resolved to a single network; report both possibilities.
+	// Base58 (legacy) addresses don't have this ambiguity.
+	let possible_networks = match (address.network, &address.payload) {
+		(Network::Testnet, Payload::WitnessProgram { .. }) => {
+			vec![Network::Testnet, Network::Signet]
 		}
+		(net, _) => vec![net],
+	};
+
+	let script_pub_key = hal::tx::OutputScriptInfo::from_script(&script_pk, address.network);
+
+	// address.payload always resolves to a concrete type, so script_pub_key carries the
+	// corresponding hash/key field; fall back to a type_ label for the rare case where the
+	// witness program's version/length combination isn't one hal recognizes.
+	let type_ = match address.payload {
+		Payload::PubkeyHash(_) => "p2pkh",
+		Payload::ScriptHash(_) => "p2sh",
 		Payload::WitnessProgram {
 			version,
-			program,
-		} => {
-			let version = version.to_num() as usize;
-			info.witness_program_version = Some(version);
-
-			if version == 0 {
-				if program.len() == 20 {
-					info.type_ = Some("p2wpkh".to_owned());
-					info.witness_pubkey_hash =
-						Some(WPubkeyHash::from_slice(&program).expect("size 20"));
-				} else if program.len() == 32 {
-					info.type_ = Some("p2wsh".to_owned());
-					info.witness_script_hash =
-						Some(WScriptHash::from_slice(&program).expect("size 32"));
-				} else {
-					info.type_ = Some("invalid-witness-program".to_owned());
-				}
-			} else {
-				info.type_ = Some("unknown-witness-program-version".to_owned());
-			}
-		}
-	}
+			ref program,
+		} => match (version.to_num(), program.len()) {
+			(0, 20) => "p2wpkh",
+			(0, 32) => "p2wsh",
+			(0, _) => "invalid-witness-program",
+			(1, 32) => "p2tr",
+			(_, _) => "unknown-witness-program-version",
+		},
+	};
+
+	let info = hal::address::AddressInfo {
+		possible_networks,
+		pubkey_hash: script_pub_key.pubkey_hash,
+		script_hash: script_pub_key.script_hash,
+		witness_pubkey_hash: script_pub_key.witness_pubkey_hash,
+		witness_script_hash: script_pub_key.witness_script_hash,
+		witness_program_version: script_pub_key.witness_program_version,
+		taproot_output_key: script_pub_key.taproot_output_key,
+		type_: Some(type_.to_owned()),
+		script_pub_key,
+	};
 
 	args.print_output(&info)
 }
+
+// This BIP-341 tweaking/control-block math is security-sensitive and easy to get subtly
+// wrong (e.g. swapped tweak operands, wrong parity bit), so unlike the rest of this repo
+// it's worth a round-trip test against `bitcoin`'s own independent control block verifier.
+#[cfg(test)]
+mod tests {
+	use bitcoin::util::taproot::{ControlBlock, LeafVersion};
+
+	use super::*;
+
+	#[test]
+	fn script_tree_control_blocks_verify() {
+		let scripts: Vec<Script> = vec![
+			Builder::new().push_int(1).into_script(),
+			Builder::new().push_int(2).into_script(),
+			Builder::new().push_int(3).into_script(),
+		];
+
+		// Exercise the exact same helpers exec_create uses to build these, so a bug fixed
+		// there is also covered here, rather than maintaining a second copy of the math.
+		let (merkle_root, paths) = build_script_tree(&scripts);
+		let (internal_key, output_key, parity) = tweak_taproot_key(*NUMS_H, merkle_root);
+
+		for (script, path) in scripts.iter().zip(paths) {
+			let control_block_bytes = leaf_control_block(internal_key, parity, &path);
+			let control_block = ControlBlock::from_slice(&control_block_bytes)
+				.expect("control block should be well-formed");
+			assert_eq!(control_block.leaf_version, LeafVersion::TapScript);
+			assert!(
+				control_block.verify_taproot_commitment(&SECP, output_key, script),
+				"control block for {} must verify against the tweaked output key",
+				script,
+			);
+		}
+	}
+}
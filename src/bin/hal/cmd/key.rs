@@ -5,14 +5,13 @@ use std::str::FromStr;
 use bitcoin::secp256k1;
 use bitcoin::hashes::hex::FromHex;
 use bitcoin::{PrivateKey, PublicKey};
-use clap;
 use rand;
 
 use hal::{self, GetInfo};
 
+use crate::util::tagged_hash;
 use crate::{SECP, cmd};
 
-
 pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand_group("key", "work with private and public keys")
 		.subcommand(cmd_generate())
@@ -20,6 +19,8 @@ pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 		.subcommand(cmd_inspect())
 		.subcommand(cmd_sign())
 		.subcommand(cmd_verify())
+		.subcommand(cmd_sign_schnorr())
+		.subcommand(cmd_verify_schnorr())
 		.subcommand(cmd_negate_pubkey())
 		.subcommand(cmd_pubkey_tweak_add())
 		.subcommand(cmd_pubkey_combine())
@@ -27,14 +28,16 @@ pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 
 pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
 	match matches.subcommand() {
-		("generate", Some(ref m)) => exec_generate(&m),
-		("derive", Some(ref m)) => exec_derive(&m),
-		("inspect", Some(ref m)) => exec_inspect(&m),
-		("sign", Some(ref m)) => exec_sign(&m),
-		("verify", Some(ref m)) => exec_verify(&m),
-		("negate-pubkey", Some(ref m)) => exec_negate_pubkey(&m),
-		("pubkey-tweak-add", Some(ref m)) => exec_pubkey_tweak_add(&m),
-		("pubkey-combine", Some(ref m)) => exec_pubkey_combine(&m),
+		("generate", Some(m)) => exec_generate(m),
+		("derive", Some(m)) => exec_derive(m),
+		("inspect", Some(m)) => exec_inspect(m),
+		("sign", Some(m)) => exec_sign(m),
+		("verify", Some(m)) => exec_verify(m),
+		("sign-schnorr", Some(m)) => exec_sign_schnorr(m),
+		("verify-schnorr", Some(m)) => exec_verify_schnorr(m),
+		("negate-pubkey", Some(m)) => exec_negate_pubkey(m),
+		("pubkey-tweak-add", Some(m)) => exec_pubkey_tweak_add(m),
+		("pubkey-combine", Some(m)) => exec_pubkey_combine(m),
 		(_, _) => unreachable!("clap prints help"),
 	};
 }
@@ -53,7 +56,7 @@ fn exec_generate<'a>(matches: &clap::ArgMatches<'a>) {
 	let secret_key = secp256k1::SecretKey::from_slice(&entropy[..]).unwrap();
 	let privkey = bitcoin::PrivateKey {
 		compressed: true,
-		network: network,
+		network,
 		inner: secret_key,
 	};
 
@@ -72,11 +75,11 @@ fn exec_derive<'a>(matches: &clap::ArgMatches<'a>) {
 
 	let privkey = {
 		let s = matches.value_of("privkey").expect("no private key provided");
-		bitcoin::PrivateKey::from_str(&s).unwrap_or_else(|_| {
+		bitcoin::PrivateKey::from_str(s).unwrap_or_else(|_| {
 			bitcoin::PrivateKey {
 				compressed: true,
-				network: network,
-				inner: secp256k1::SecretKey::from_str(&s)
+				network,
+				inner: secp256k1::SecretKey::from_str(s)
 					.expect("invalid private key provided"),
 			}
 		})
@@ -94,9 +97,9 @@ fn cmd_inspect<'a>() -> clap::App<'a, 'a> {
 fn exec_inspect<'a>(matches: &clap::ArgMatches<'a>) {
 	let raw = matches.value_of("key").expect("no key provided");
 
-	let info = if let Ok(privkey) = PrivateKey::from_str(&raw) {
+	let info = if let Ok(privkey) = PrivateKey::from_str(raw) {
 		privkey.get_info(privkey.network)
-	} else if let Ok(sk) = secp256k1::SecretKey::from_str(&raw) {
+	} else if let Ok(sk) = secp256k1::SecretKey::from_str(raw) {
 		sk.get_info(cmd::network(matches))
 	} else {
 		panic!("invalid WIF/hex private key: {}", raw);
@@ -123,7 +126,7 @@ fn exec_sign<'a>(matches: &clap::ArgMatches<'a>) {
 	let network = cmd::network(matches);
 
 	let msg_hex = matches.value_of("message").expect("no message given");
-	let mut msg_bytes = hex::decode(&msg_hex).expect("invalid hex message");
+	let mut msg_bytes = hex::decode(msg_hex).expect("invalid hex message");
 	if matches.is_present("reverse") {
 		msg_bytes.reverse();
 	}
@@ -131,11 +134,11 @@ fn exec_sign<'a>(matches: &clap::ArgMatches<'a>) {
 
 	let privkey = {
 		let s = matches.value_of("privkey").expect("no private key provided");
-		bitcoin::PrivateKey::from_str(&s).unwrap_or_else(|_| {
+		bitcoin::PrivateKey::from_str(s).unwrap_or_else(|_| {
 			bitcoin::PrivateKey {
 				compressed: true,
-				network: network,
-				inner: secp256k1::SecretKey::from_str(&s).expect("invalid private key provided"),
+				network,
+				inner: secp256k1::SecretKey::from_str(s).expect("invalid private key provided"),
 			}
 		})
 	};
@@ -162,7 +165,7 @@ fn cmd_verify<'a>() -> clap::App<'a, 'a> {
 
 fn exec_verify<'a>(matches: &clap::ArgMatches<'a>) {
 	let msg_hex = matches.value_of("message").expect("no message given");
-	let mut msg_bytes = hex::decode(&msg_hex).expect("invalid hex message");
+	let mut msg_bytes = hex::decode(msg_hex).expect("invalid hex message");
 	if matches.is_present("reverse") {
 		msg_bytes.reverse();
 	}
@@ -171,7 +174,7 @@ fn exec_verify<'a>(matches: &clap::ArgMatches<'a>) {
 	let pubkey = pubkey_hex.parse::<PublicKey>().expect("invalid public key");
 	let sig = {
 		let hex = matches.value_of("signature").expect("no signature provided");
-		let bytes = hex::decode(&hex).expect("invalid signature: not hex");
+		let bytes = hex::decode(hex).expect("invalid signature: not hex");
 		if bytes.len() == 64 {
 			secp256k1::ecdsa::Signature::from_compact(&bytes).expect("invalid signature")
 		} else {
@@ -204,6 +207,97 @@ fn exec_verify<'a>(matches: &clap::ArgMatches<'a>) {
 	}
 }
 
+fn cmd_sign_schnorr<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("sign-schnorr", "create Schnorr signatures (BIP-340)").args(&[
+		cmd::opt_yaml(),
+		cmd::opt(
+			"tweak",
+			"apply the BIP-341 key tweak before signing; value is the merkle root in hex, \
+			or an empty string for a key-path spend with no script tree",
+		).takes_value(true).required(false),
+		cmd::arg("privkey", "the private key in hex or WIF").required(true),
+		cmd::arg("message", "the message to be signed in hex (must be 32 bytes)").required(true),
+	])
+}
+
+fn exec_sign_schnorr<'a>(matches: &clap::ArgMatches<'a>) {
+	let network = cmd::network(matches);
+
+	let msg_hex = matches.value_of("message").expect("no message given");
+	let msg_bytes = hex::decode(msg_hex).expect("invalid hex message");
+	let msg = secp256k1::Message::from_slice(&msg_bytes[..]).expect("invalid message to be signed");
+
+	let privkey = {
+		let s = matches.value_of("privkey").expect("no private key provided");
+		bitcoin::PrivateKey::from_str(s).unwrap_or_else(|_| {
+			bitcoin::PrivateKey {
+				compressed: true,
+				network,
+				inner: secp256k1::SecretKey::from_str(s).expect("invalid private key provided"),
+			}
+		})
+	};
+	let keypair = secp256k1::KeyPair::from_secret_key(&SECP, &privkey.inner);
+
+	let keypair = if let Some(tweak_hex) = matches.value_of("tweak") {
+		let merkle_root = hex::decode(tweak_hex).expect("invalid merkle root hex");
+		let (xonly, _parity) = keypair.x_only_public_key();
+		let tweak_data = [&xonly.serialize()[..], &merkle_root[..]].concat();
+		let tweak = secp256k1::Scalar::from_be_bytes(tagged_hash("TapTweak", &tweak_data))
+			.expect("invalid tweak");
+		keypair.add_xonly_tweak(&SECP, &tweak).expect("invalid tweak")
+	} else {
+		keypair
+	};
+
+	let signature = SECP.sign_schnorr(&msg, &keypair);
+	cmd::print_output(matches, &signature.get_info(network))
+}
+
+fn cmd_verify_schnorr<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("verify-schnorr", "verify Schnorr signatures (BIP-340)").args(&[
+		cmd::opt_yaml(),
+		cmd::arg("message", "the message to be signed in hex (must be 32 bytes)").required(true),
+		cmd::arg("pubkey", "the public key in hex, compressed (33 bytes) or x-only (32 bytes)")
+			.required(true),
+		cmd::arg("signature", "the Schnorr signature in hex").required(true),
+	])
+}
+
+fn exec_verify_schnorr<'a>(matches: &clap::ArgMatches<'a>) {
+	let msg_hex = matches.value_of("message").expect("no message given");
+	let msg_bytes = hex::decode(msg_hex).expect("invalid hex message");
+	let msg = secp256k1::Message::from_slice(&msg_bytes[..]).expect("invalid message to be signed");
+
+	let pubkey_bytes = {
+		let hex = matches.value_of("pubkey").expect("no public key provided");
+		hex::decode(hex).expect("invalid public key: not hex")
+	};
+	let xonly_pubkey = match pubkey_bytes.len() {
+		32 => secp256k1::XOnlyPublicKey::from_slice(&pubkey_bytes)
+			.expect("invalid x-only public key"),
+		33 => secp256k1::PublicKey::from_slice(&pubkey_bytes)
+			.expect("invalid public key")
+			.into(),
+		_ => panic!("public key must be 32 (x-only) or 33 (compressed) bytes"),
+	};
+
+	let sig = {
+		let hex = matches.value_of("signature").expect("no signature provided");
+		let bytes = hex::decode(hex).expect("invalid signature: not hex");
+		secp256k1::schnorr::Signature::from_slice(&bytes).expect("invalid Schnorr signature")
+	};
+
+	let valid = SECP.verify_schnorr(&sig, &msg, &xonly_pubkey).is_ok();
+
+	if valid {
+		eprintln!("Signature is valid.");
+	} else {
+		eprintln!("Signature is invalid!");
+		process::exit(1);
+	}
+}
+
 fn cmd_negate_pubkey<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand("negate-pubkey", "negate the public key")
 		.args(&[cmd::opt_yaml(), cmd::arg("pubkey", "the public key").required(true)])
@@ -211,7 +305,7 @@ fn cmd_negate_pubkey<'a>() -> clap::App<'a, 'a> {
 
 fn exec_negate_pubkey<'a>(matches: &clap::ArgMatches<'a>) {
 	let s = matches.value_of("pubkey").expect("no public key provided");
-	let key = PublicKey::from_str(&s).expect("invalid public key");
+	let key = PublicKey::from_str(s).expect("invalid public key");
 
 	let negated = key.inner.negate(&SECP);
 
@@ -240,9 +334,9 @@ fn exec_pubkey_tweak_add<'a>(matches: &clap::ArgMatches<'a>) {
 		secp256k1::Scalar::from_be_bytes(bytes).expect("invalid scalar")
 	};
 
-	match point.inner.add_exp_tweak(&SECP, &scalar.into()) {
+	match point.inner.add_exp_tweak(&SECP, &scalar) {
 		Ok(..) => {
-			eprintln!("{}", point.to_string());
+			eprintln!("{}", point);
 		}
 		Err(err) => {
 			eprintln!("error: {}", err);
@@ -272,7 +366,7 @@ fn exec_pubkey_combine<'a>(matches: &clap::ArgMatches<'a>) {
 
 	match pk1.inner.combine(&pk2.inner) {
 		Ok(sum) => {
-			eprintln!("{}", sum.to_string());
+			eprintln!("{}", sum);
 		}
 		Err(err) => {
 			eprintln!("error: {}", err);
@@ -1,32 +1,19 @@
+use std::io;
+
+use bitcoin::Network;
+use serde::Serialize;
+
 pub mod address;
-pub mod bech32;
-pub mod bip32;
-pub mod bip39;
-pub mod block;
 pub mod key;
-pub mod ln;
-pub mod message;
-pub mod miniscript;
-pub mod psbt;
 pub mod script;
-pub mod tx;
+
+// NB this chunk of the hal CLI only carries the address/key/script commands; the other
+// built-in subcommands (bech32, bip32, bip39, block, ln, message, miniscript, psbt, tx)
+// live in the rest of the hal repository and aren't part of this slice.
 
 /// Build a list of all built-in subcommands.
 pub fn subcommands() -> Vec<clap::App<'static, 'static>> {
-	vec![
-		address::subcommand(),
-		bech32::subcommand(),
-		block::subcommand(),
-		key::subcommand(),
-		ln::subcommand(),
-		message::subcommand(),
-		miniscript::subcommand(),
-		tx::subcommand(),
-		psbt::subcommand(),
-		script::subcommand(),
-		bip32::subcommand(),
-		bip39::subcommand(),
-	]
+	vec![address::subcommand(), key::subcommand(), script::subcommand()]
 }
 
 /// Create a new subcommand group using the template that sets all the common settings.
@@ -47,3 +34,51 @@ pub fn subcommand<'a>(name: &'a str, about: &'a str) -> clap::App<'a, 'a> {
 		.about(about)
 		.setting(clap::AppSettings::DisableHelpSubcommand)
 }
+
+/// The `--yaml` flag shared by all commands that print structured output.
+pub fn opt_yaml<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name("yaml").long("yaml").short("y").help("print output in YAML instead of JSON")
+}
+
+/// Build an optional named flag/option.
+pub fn opt<'a>(name: &'a str, help: &'a str) -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(name).long(name).help(help)
+}
+
+/// Build a positional argument.
+pub fn arg<'a>(name: &'a str, help: &'a str) -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(name).help(help)
+}
+
+/// The network-selection flags shared by `address create` and the `key` subcommands.
+/// Defaults to mainnet when none of these are given.
+pub fn opts_networks<'a>() -> Vec<clap::Arg<'a, 'a>> {
+	vec![
+		opt("testnet", "run in testnet mode").short("t"),
+		opt("signet", "run in signet mode"),
+		opt("regtest", "run in regtest mode"),
+	]
+}
+
+/// Determine the selected network from the flags added by [opts_networks].
+pub fn network<'a>(matches: &clap::ArgMatches<'a>) -> Network {
+	if matches.is_present("testnet") {
+		Network::Testnet
+	} else if matches.is_present("signet") {
+		Network::Signet
+	} else if matches.is_present("regtest") {
+		Network::Regtest
+	} else {
+		Network::Bitcoin
+	}
+}
+
+/// Print a command's output as JSON, or as YAML when `--yaml` was given.
+pub fn print_output<'a, T: Serialize>(matches: &clap::ArgMatches<'a>, info: &T) {
+	if matches.is_present("yaml") {
+		serde_yaml::to_writer(io::stdout(), info).expect("error writing YAML output");
+	} else {
+		serde_json::to_writer_pretty(io::stdout(), info).expect("error writing JSON output");
+		println!();
+	}
+}
@@ -0,0 +1,34 @@
+
+use bitcoin::Script;
+
+use hal;
+
+use crate::prelude::*;
+
+pub fn subcommand<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand_group("script", "work with scripts").subcommand(cmd_inspect())
+}
+
+pub fn execute<'a>(args: &clap::ArgMatches<'a>) {
+	match args.subcommand() {
+		("inspect", Some(m)) => exec_inspect(m),
+		(_, _) => unreachable!("clap prints help"),
+	};
+}
+
+fn cmd_inspect<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("inspect", "inspect a scriptPubKey")
+		.args(&args::opts_networks())
+		.args(&[args::opt_yaml(), args::arg("script", "the script in hex").required(true)])
+}
+
+fn exec_inspect<'a>(args: &clap::ArgMatches<'a>) {
+	let network = args.network();
+
+	let script_hex = args.value_of("script").expect("no script provided");
+	let script_bytes = hex::decode(script_hex).expect("invalid script hex");
+	let script: Script = script_bytes.into();
+
+	let info = hal::tx::OutputScriptInfo::from_script(&script, network);
+	args.print_output(&info)
+}
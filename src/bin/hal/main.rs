@@ -0,0 +1,34 @@
+#[macro_use]
+extern crate lazy_static;
+
+use bitcoin::secp256k1::{All, Secp256k1};
+
+pub(crate) mod args;
+pub(crate) mod cmd;
+mod prelude;
+pub(crate) mod util;
+
+lazy_static! {
+	/// The global secp256k1 context used throughout the CLI.
+	pub static ref SECP: Secp256k1<All> = Secp256k1::new();
+}
+
+fn main() {
+	let app = clap::App::new("hal")
+		.about("hal - the Bitcoin companion")
+		.settings(&[
+			clap::AppSettings::SubcommandRequiredElseHelp,
+			clap::AppSettings::DisableHelpSubcommand,
+			clap::AppSettings::VersionlessSubcommands,
+			clap::AppSettings::UnifiedHelpMessage,
+		])
+		.subcommands(cmd::subcommands());
+
+	let matches = app.get_matches();
+	match matches.subcommand() {
+		("address", Some(m)) => cmd::address::execute(m),
+		("key", Some(m)) => cmd::key::execute(m),
+		("script", Some(m)) => cmd::script::execute(m),
+		(cmd, _) => unreachable!("unknown subcommand {}", cmd),
+	}
+}
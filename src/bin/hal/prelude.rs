@@ -0,0 +1,8 @@
+//! Common imports for subcommand implementations.
+
+pub use bitcoin::secp256k1;
+
+pub(crate) use crate::args::{self, ArgMatchesExt};
+pub(crate) use crate::cmd;
+pub(crate) use crate::util;
+pub(crate) use crate::SECP;
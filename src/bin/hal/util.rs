@@ -0,0 +1,18 @@
+//! Small helpers shared across subcommands.
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+
+/// Returns true if more than one of the given booleans is true.
+pub fn more_than_one(bools: &[bool]) -> bool {
+	bools.iter().filter(|b| **b).count() > 1
+}
+
+/// Calculate a BIP-340 tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || data)`.
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+	let tag_hash = sha256::Hash::hash(tag.as_bytes());
+	let mut engine = sha256::Hash::engine();
+	engine.input(&tag_hash[..]);
+	engine.input(&tag_hash[..]);
+	engine.input(data);
+	sha256::Hash::from_engine(engine).into_inner()
+}
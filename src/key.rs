@@ -0,0 +1,70 @@
+//! Key- and signature-related types used by the `key` command.
+
+use bitcoin::{Network, PrivateKey, PublicKey};
+use secp256k1::{ecdsa, schnorr, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::{GetInfo, HexBytes};
+
+/// Information about a private key, to be used in `key generate`/`key derive`/`key inspect`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivateKeyInfo {
+	pub network: Network,
+	pub compressed: bool,
+	pub wif: String,
+	pub hex: HexBytes,
+	pub public_key: PublicKey,
+}
+
+impl GetInfo<PrivateKeyInfo> for PrivateKey {
+	fn get_info(&self, _network: Network) -> PrivateKeyInfo {
+		PrivateKeyInfo {
+			network: self.network,
+			compressed: self.compressed,
+			wif: self.to_string(),
+			hex: self.inner[..].to_vec().into(),
+			public_key: self.public_key(secp256k1::SECP256K1),
+		}
+	}
+}
+
+impl GetInfo<PrivateKeyInfo> for SecretKey {
+	fn get_info(&self, network: Network) -> PrivateKeyInfo {
+		PrivateKey {
+			compressed: true,
+			network,
+			inner: *self,
+		}
+		.get_info(network)
+	}
+}
+
+/// Information about an ECDSA signature, to be used in `key sign`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcdsaSignatureInfo {
+	pub hex: HexBytes,
+	pub der_hex: HexBytes,
+}
+
+impl GetInfo<EcdsaSignatureInfo> for ecdsa::Signature {
+	fn get_info(&self, _network: Network) -> EcdsaSignatureInfo {
+		EcdsaSignatureInfo {
+			hex: self.serialize_compact().to_vec().into(),
+			der_hex: self.serialize_der().to_vec().into(),
+		}
+	}
+}
+
+/// Information about a Schnorr (BIP-340) signature, to be used in `key sign-schnorr`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchnorrSignatureInfo {
+	pub hex: HexBytes,
+}
+
+impl GetInfo<SchnorrSignatureInfo> for schnorr::Signature {
+	fn get_info(&self, _network: Network) -> SchnorrSignatureInfo {
+		SchnorrSignatureInfo {
+			hex: self.as_ref().to_vec().into(),
+		}
+	}
+}
@@ -0,0 +1,48 @@
+extern crate bitcoin;
+extern crate secp256k1;
+extern crate serde;
+
+pub mod address;
+pub mod key;
+pub mod tx;
+
+use std::fmt;
+
+use bitcoin::Network;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A trait used to convert a native bitcoin type into a struct that is
+/// suitable for dumping as YAML or JSON.
+pub trait GetInfo<T> {
+	/// Get the info.
+	fn get_info(&self, network: Network) -> T;
+}
+
+/// A wrapper around a byte vector that (de)serializes from/to a hex string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexBytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for HexBytes {
+	fn from(b: Vec<u8>) -> HexBytes {
+		HexBytes(b)
+	}
+}
+
+impl fmt::Display for HexBytes {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", hex::encode(&self.0))
+	}
+}
+
+impl Serialize for HexBytes {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&hex::encode(&self.0))
+	}
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		Ok(HexBytes(hex::decode(&s).map_err(serde::de::Error::custom)?))
+	}
+}
@@ -0,0 +1,99 @@
+//! Transaction- and script-related types, shared by `address inspect` and `script inspect`.
+
+use bitcoin::hashes::Hash;
+use bitcoin::util::address::Payload;
+use bitcoin::{Address, Network, PubkeyHash, Script, ScriptHash, WPubkeyHash, WScriptHash};
+use secp256k1::XOnlyPublicKey;
+use serde::{Deserialize, Serialize};
+
+use crate::HexBytes;
+
+/// Information about a scriptPubKey, classified by output type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputScriptInfo {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub hex: Option<HexBytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub asm: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub address: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub type_: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pubkey_hash: Option<PubkeyHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub script_hash: Option<ScriptHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub witness_pubkey_hash: Option<WPubkeyHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub witness_script_hash: Option<WScriptHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub witness_program_version: Option<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub taproot_output_key: Option<XOnlyPublicKey>,
+}
+
+impl OutputScriptInfo {
+	/// Classify a scriptPubKey and describe it, reusing the same length/opcode checks as
+	/// upstream rust-bitcoin's `Script::is_p2pk`/`is_v0_p2wpkh`/`is_v0_p2wsh`/etc.
+	///
+	/// Where possible, also extracts the embedded pubkey hash, script hash, witness program
+	/// or taproot output key, by decoding the address this scriptPubKey resolves to on
+	/// `network`.
+	pub fn from_script(script: &Script, network: Network) -> OutputScriptInfo {
+		let type_ = if script.is_p2pk() {
+			Some("p2pk")
+		} else if script.is_p2pkh() {
+			Some("p2pkh")
+		} else if script.is_p2sh() {
+			Some("p2sh")
+		} else if script.is_v0_p2wpkh() {
+			Some("p2wpkh")
+		} else if script.is_v0_p2wsh() {
+			Some("p2wsh")
+		} else if script.is_v1_p2tr() {
+			Some("p2tr")
+		} else if script.is_op_return() || script.is_provably_unspendable() {
+			Some("op_return")
+		} else {
+			None
+		};
+
+		let mut info = OutputScriptInfo {
+			hex: Some(script.to_bytes().into()),
+			asm: Some(script.asm()),
+			address: None,
+			type_: type_.map(str::to_owned),
+			pubkey_hash: None,
+			script_hash: None,
+			witness_pubkey_hash: None,
+			witness_script_hash: None,
+			witness_program_version: None,
+			taproot_output_key: None,
+		};
+
+		if let Ok(address) = Address::from_script(script, network) {
+			match &address.payload {
+				Payload::PubkeyHash(pkh) => info.pubkey_hash = Some(*pkh),
+				Payload::ScriptHash(sh) => info.script_hash = Some(*sh),
+				Payload::WitnessProgram {
+					version,
+					program,
+				} => {
+					let version = version.to_num() as usize;
+					info.witness_program_version = Some(version);
+					if version == 0 && program.len() == 20 {
+						info.witness_pubkey_hash = WPubkeyHash::from_slice(program).ok();
+					} else if version == 0 && program.len() == 32 {
+						info.witness_script_hash = WScriptHash::from_slice(program).ok();
+					} else if version == 1 && program.len() == 32 {
+						info.taproot_output_key = XOnlyPublicKey::from_slice(program).ok();
+					}
+				}
+			}
+			info.address = Some(address);
+		}
+
+		info
+	}
+}